@@ -1,8 +1,12 @@
-mod msg;
+pub mod msg;
 mod player;
-mod update;
+pub mod update;
 
 pub use msg::{
+    Color,
+    Importance,
+    Kind,
+    Log,
     Message,
     Text
 };
@@ -16,6 +20,10 @@ pub use player::{
 
 pub use update::{
     Effect,
+    EffectType,
+    EntityId,
+    Priority,
+    StatusEffects,
     Updatable,
     UpdateQueue
 };
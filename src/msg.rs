@@ -1,4 +1,7 @@
+use crate::update::Effect;
+
 use std::{
+    collections::HashSet,
     fmt,
     fmt::{Debug, Formatter}
 };
@@ -11,7 +14,7 @@ pub struct Message {
     pub contents: Vec<Text>
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
 pub enum Kind {
     Display,
     Debug,
@@ -19,7 +22,7 @@ pub enum Kind {
     Error
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
 pub enum Importance {
     Hidden,
     Verbose,
@@ -103,6 +106,82 @@ impl Text {
     }
 }
 
+impl Color {
+    /// The ANSI SGR parameter for this color used as a foreground color.
+    fn ansi_fg(&self) -> String {
+        match self {
+            Color::Default => String::from("39"),
+            Color::White => String::from("97"),
+            Color::Gray => String::from("90"),
+            Color::Black => String::from("30"),
+            Color::Red => String::from("31"),
+            Color::Orange => String::from("33"),
+            Color::Yellow => String::from("93"),
+            Color::Green => String::from("32"),
+            Color::Pink => String::from("95"),
+            Color::Blue => String::from("34"),
+            Color::Rgb(r, g, b) => format!("38;2;{};{};{}", r, g, b)
+        }
+    }
+
+    /// The ANSI SGR parameter for this color used as a background color.
+    fn ansi_bg(&self) -> String {
+        match self {
+            Color::Default => String::from("49"),
+            Color::White => String::from("107"),
+            Color::Gray => String::from("100"),
+            Color::Black => String::from("40"),
+            Color::Red => String::from("41"),
+            Color::Orange => String::from("43"),
+            Color::Yellow => String::from("103"),
+            Color::Green => String::from("42"),
+            Color::Pink => String::from("105"),
+            Color::Blue => String::from("44"),
+            Color::Rgb(r, g, b) => format!("48;2;{};{};{}", r, g, b)
+        }
+    }
+}
+
+/// Rendering Message/Text to something a front-end can actually display.
+impl Text {
+    /// Render this run of text as an ANSI-escaped string, honoring `bold`, `italic`, `color`,
+    /// and `background_color`. The escape is reset at the end of the run so styling never
+    /// bleeds into whatever follows.
+    pub fn render_ansi(&self) -> String {
+        let mut codes = vec![self.color.ansi_fg(), self.background_color.ansi_bg()];
+
+        if self.bold {
+            codes.push(String::from("1"));
+        }
+
+        if self.italic {
+            codes.push(String::from("3"));
+        }
+
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), self.text)
+    }
+}
+
+impl Message {
+    /// Render this message as a single ANSI-escaped string for terminal display, with each
+    /// `Text` run styled according to its `bold`, `italic`, `color`, and `background_color`.
+    pub fn render_ansi(&self) -> String {
+        self.contents.iter()
+            .map(Text::render_ansi)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Render this message as plain, unstyled text, for sinks (log files, non-TTY output) that
+    /// shouldn't receive ANSI escapes.
+    pub fn render_plain(&self) -> String {
+        self.contents.iter()
+            .map(|text| text.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
 // Debug implementations for Message and Text - for testing/debugging
 
 impl Debug for Message {
@@ -119,4 +198,107 @@ impl Debug for Text {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.write_str(&self.text)
     }
+}
+
+/// Collects `Effect::Log` messages drained from the update loop and hands them back to a
+/// front-end as filtered, coalesced batches.
+///
+/// A front-end usually advances several entity updates before it next redraws, so rather than
+/// streaming each message the moment it's produced, `Log` buffers them (in the order they were
+/// pushed, which is already chronological order since the update queue always processes the
+/// earliest-scheduled entity first) and hands them back as one normalized batch from `flush`.
+pub struct Log {
+    min_importance: Importance,
+    allowed_kinds: Option<HashSet<Kind>>,
+    collapse_repeats: bool,
+    buffer: Vec<Message>
+}
+
+impl Default for Log {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Log {
+    /// Create a log writer that accepts every importance and kind, and collapses consecutive
+    /// identical messages into a single "(xN)" message.
+    pub fn new() -> Self {
+        Log {
+            min_importance: Importance::Hidden,
+            allowed_kinds: None,
+            collapse_repeats: true,
+            buffer: Vec::new()
+        }
+    }
+
+    /// Suppress any message whose importance is below `importance`. Defaults to
+    /// `Importance::Hidden`, which accepts everything.
+    pub fn set_min_importance(&mut self, importance: Importance) {
+        self.min_importance = importance;
+    }
+
+    /// Restrict buffered messages to the given kinds, or accept every kind if `kinds` is `None`.
+    pub fn set_kind_filter(&mut self, kinds: Option<HashSet<Kind>>) {
+        self.allowed_kinds = kinds;
+    }
+
+    /// Enable or disable collapsing consecutive identical messages into a single "(xN)" message.
+    pub fn set_collapse_repeats(&mut self, collapse: bool) {
+        self.collapse_repeats = collapse;
+    }
+
+    /// Buffer an effect's message, unless it's suppressed by the importance or kind filter.
+    /// Effects other than `Effect::Log` are ignored, since `Log` only deals in messages.
+    pub fn push(&mut self, effect: Effect) {
+        let message = match effect {
+            Effect::Log(message) => message,
+            _ => return
+        };
+
+        if message.importance < self.min_importance {
+            return;
+        }
+
+        if self.allowed_kinds.as_ref().is_some_and(|kinds| !kinds.contains(&message.kind)) {
+            return;
+        }
+
+        self.buffer.push(message);
+    }
+
+    /// Drain the buffer, returning every accepted message in chronological order. If
+    /// `collapse_repeats` is enabled (the default), runs of consecutive identical messages are
+    /// collapsed into a single message suffixed with "(xN)".
+    pub fn flush(&mut self) -> Vec<Message> {
+        let drained: Vec<Message> = self.buffer.drain(..).collect();
+
+        if !self.collapse_repeats {
+            return drained;
+        }
+
+        let mut grouped: Vec<(Message, u32)> = Vec::new();
+
+        for message in drained {
+            match grouped.last_mut() {
+                Some((last, count)) if last.kind == message.kind
+                    && last.importance == message.importance
+                    && last.hidden == message.hidden
+                    && last.render_ansi() == message.render_ansi() => {
+                    *count += 1;
+                },
+                _ => grouped.push((message, 1))
+            }
+        }
+
+        grouped.into_iter()
+            .map(|(mut message, count)| {
+                if count > 1 {
+                    message.contents.push(Text::normal(&format!("(x{})", count)));
+                }
+
+                message
+            })
+            .collect()
+    }
 }
\ No newline at end of file
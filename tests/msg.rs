@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+
+use yanor_core::{
+    Color,
+    Importance,
+    Kind,
+    Log,
+    Message,
+    Text,
+    update::Effect
+};
+
+#[test]
+fn render_plain_joins_text_runs() {
+    let msg = Message::normal(vec![
+        Text::normal("Message from"),
+        Text::bold("Jessie"),
+        Text::normal(":"),
+        Text::italic("Hello!")
+    ]);
+
+    assert_eq!(msg.render_plain(), "Message from Jessie : Hello!");
+}
+
+#[test]
+fn render_ansi_applies_bold_and_italic() {
+    let msg = Message::normal(vec![Text::bold("shout"), Text::italic("whisper")]);
+    let rendered = msg.render_ansi();
+
+    assert!(rendered.contains("\x1b[39;49;1mshout\x1b[0m"));
+    assert!(rendered.contains("\x1b[39;49;3mwhisper\x1b[0m"));
+}
+
+#[test]
+fn render_ansi_applies_rgb_foreground_and_background() {
+    let mut text = Text::normal("colored");
+    text.color = Color::Rgb(10, 20, 30);
+    text.background_color = Color::Rgb(1, 2, 3);
+
+    let rendered = text.render_ansi();
+
+    assert_eq!(rendered, "\x1b[38;2;10;20;30;48;2;1;2;3mcolored\x1b[0m");
+}
+
+fn msg(text: &str) -> Message {
+    Message::normal(vec![Text::normal(text)])
+}
+
+#[test]
+fn log_suppresses_below_min_importance() {
+    let mut log = Log::new();
+    log.set_min_importance(Importance::Normal);
+
+    let mut verbose = msg("too quiet");
+    verbose.importance = Importance::Verbose;
+
+    log.push(Effect::Log(verbose));
+    log.push(Effect::Log(msg("loud enough")));
+
+    let flushed = log.flush();
+
+    assert_eq!(flushed.len(), 1);
+    assert_eq!(flushed[0].render_plain(), "loud enough");
+}
+
+#[test]
+fn log_filters_by_kind() {
+    let mut log = Log::new();
+
+    let mut kinds = HashSet::new();
+    kinds.insert(Kind::Error);
+    log.set_kind_filter(Some(kinds));
+
+    let mut warning = msg("heads up");
+    warning.kind = Kind::Warning;
+
+    let mut error = msg("on fire");
+    error.kind = Kind::Error;
+
+    log.push(Effect::Log(warning));
+    log.push(Effect::Log(error));
+
+    let flushed = log.flush();
+
+    assert_eq!(flushed.len(), 1);
+    assert_eq!(flushed[0].render_plain(), "on fire");
+}
+
+#[test]
+fn log_collapses_consecutive_repeats() {
+    let mut log = Log::new();
+
+    log.push(Effect::Log(msg("You are hit.")));
+    log.push(Effect::Log(msg("You are hit.")));
+    log.push(Effect::Log(msg("You are hit.")));
+    log.push(Effect::Log(msg("Something else.")));
+
+    let flushed = log.flush();
+
+    assert_eq!(flushed.len(), 2);
+    assert_eq!(flushed[0].render_plain(), "You are hit. (x3)");
+    assert_eq!(flushed[1].render_plain(), "Something else.");
+}
+
+#[test]
+fn log_flush_empties_the_buffer() {
+    let mut log = Log::new();
+    log.push(Effect::Log(msg("one shot")));
+
+    assert_eq!(log.flush().len(), 1);
+    assert_eq!(log.flush().len(), 0);
+}
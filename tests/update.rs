@@ -3,6 +3,8 @@ use yanor_core::{
     UpdateQueue,
     Message,
     Text,
+    EffectType,
+    StatusEffects,
     update::Effect
 };
 
@@ -60,8 +62,8 @@ fn ordering() {
     let mut dummy1 = Dummy1 { update_count: 0, max_updates: 10 };
     let mut dummy2 = Dummy2 { has_updated: false };
 
-    queue.push(10, &mut dummy1);
-    queue.push(0, &mut dummy2);
+    queue.push(0, 10, 0, &mut dummy1);
+    queue.push(1, 0, 0, &mut dummy2);
 
     let mut times = [0; 13];
     let mut i = 0;
@@ -115,8 +117,8 @@ fn effects() {
     let mut messenger1 = Messenger::new("Jessie", "Hello!");
     let mut messenger2 = Messenger::new("tester", "testing");
 
-    queue.push(0, &mut messenger1);
-    queue.push(1, &mut messenger2);
+    queue.push(0, 0, 0, &mut messenger1);
+    queue.push(1, 1, 0, &mut messenger2);
 
     let mut effects: Vec<Effect> = Vec::new();
     let mut log: Vec<Message> = Vec::with_capacity(2);
@@ -124,11 +126,308 @@ fn effects() {
     while let Some(_) = queue.update(&mut effects) {
         for effect in effects.drain(..) {
             match effect {
-                Effect::Log(msg) => log.push(msg)
+                Effect::Log(msg) => log.push(msg),
+                _ => { }
             }
         }
     }
 
     assert_eq!(format!("{:?}", log[0]), format!("{:?}", messenger1.msg));
     assert_eq!(format!("{:?}", log[1]), format!("{:?}", messenger2.msg));
+}
+
+#[test]
+fn priority_tie_break() {
+    let mut queue = UpdateQueue::new();
+    let mut messenger1 = Messenger::new("player", "acts first");
+    let mut messenger2 = Messenger::new("monster", "acts second");
+    let mut messenger3 = Messenger::new("rubble", "acts last");
+
+    // All three are scheduled for the same tick; higher priority should pop first, and equal
+    // priorities should fall back to insertion order.
+    queue.push(0, 5, 10, &mut messenger2);
+    queue.push(1, 5, 20, &mut messenger1);
+    queue.push(2, 5, 10, &mut messenger3);
+
+    let mut effects: Vec<Effect> = Vec::new();
+    let mut log: Vec<Message> = Vec::with_capacity(3);
+
+    while let Some(_) = queue.update(&mut effects) {
+        for effect in effects.drain(..) {
+            match effect {
+                Effect::Log(msg) => log.push(msg),
+                _ => { }
+            }
+        }
+    }
+
+    assert_eq!(format!("{:?}", log[0]), format!("{:?}", messenger1.msg));
+    assert_eq!(format!("{:?}", log[1]), format!("{:?}", messenger2.msg));
+    assert_eq!(format!("{:?}", log[2]), format!("{:?}", messenger3.msg));
+}
+
+struct Bleeder {
+    status: StatusEffects,
+    now: u32
+}
+
+impl Updatable for Bleeder {
+    fn update(&mut self, effects: &mut Vec<Effect>) -> Option<u32> {
+        // Track which of our own statuses are still active as of this tick; the queue itself
+        // (not this entity) is responsible for announcing ExpireStatus/the wear-off message at
+        // the right tick - see the ApplyStatus pushed below.
+        self.status.expire(self.now);
+
+        // Bleed is applied on the first tick and refreshed (not re-applied) on the second.
+        if self.now == 0 || self.now == 1 {
+            self.status.apply(EffectType::Bleed, self.now + 3);
+            effects.push(Effect::ApplyStatus { target: 0, kind: EffectType::Bleed, duration: 3 });
+        }
+
+        if self.status.is_active(EffectType::Bleed) {
+            effects.push(Effect::Log(Message::normal(vec![Text::normal("You take bleed damage.")])));
+        }
+
+        self.now += 1;
+
+        if self.now <= 10 {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn deactivate(&mut self) {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn status_effect_wear_off() {
+    let mut queue = UpdateQueue::new();
+    let mut bleeder = Bleeder { status: StatusEffects::new(), now: 0 };
+
+    queue.push(0, 0, 0, &mut bleeder);
+
+    let mut effects: Vec<Effect> = Vec::new();
+    let mut applies = 0;
+    let mut expires = 0;
+    let mut wear_off_logs = 0;
+
+    while let Some(_) = queue.update(&mut effects) {
+        for effect in effects.drain(..) {
+            match effect {
+                Effect::ApplyStatus { kind: EffectType::Bleed, .. } => applies += 1,
+                Effect::ExpireStatus { kind: EffectType::Bleed, .. } => expires += 1,
+                Effect::Log(msg) => {
+                    if format!("{:?}", msg) == format!("{:?}", EffectType::Bleed.wear_off_message()) {
+                        wear_off_logs += 1;
+                    }
+                },
+                _ => { }
+            }
+        }
+    }
+
+    // The refresh on tick 1 must not schedule a second, phantom expiry/wear-off.
+    assert_eq!(applies, 2);
+    assert_eq!(expires, 1);
+    assert_eq!(wear_off_logs, 1);
+}
+
+struct OneShotApplier {
+    applied: bool
+}
+
+impl Updatable for OneShotApplier {
+    fn update(&mut self, effects: &mut Vec<Effect>) -> Option<u32> {
+        if self.applied {
+            return None;
+        }
+
+        self.applied = true;
+        effects.push(Effect::ApplyStatus { target: 0, kind: EffectType::Poison, duration: 3 });
+
+        // Overshoots the expiry by a wide margin - the queue must not wait for this entity's own
+        // next update to notice (and announce) the expiry.
+        Some(100)
+    }
+
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn deactivate(&mut self) {
+        unimplemented!()
+    }
+}
+
+struct Ticker {
+    count: u32
+}
+
+impl Updatable for Ticker {
+    fn update(&mut self, _effects: &mut Vec<Effect>) -> Option<u32> {
+        self.count += 1;
+
+        if self.count < 10 {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn deactivate(&mut self) {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn status_expiry_fires_precisely_even_if_applier_overshoots() {
+    let mut queue = UpdateQueue::new();
+    let mut applier = OneShotApplier { applied: false };
+    let mut ticker = Ticker { count: 0 };
+
+    queue.push(0, 0, 0, &mut applier);
+    queue.push(1, 0, 0, &mut ticker);
+
+    let mut effects: Vec<Effect> = Vec::new();
+    let mut expired_at = None;
+
+    while let Some(now) = queue.update(&mut effects) {
+        for effect in effects.drain(..) {
+            if let Effect::ExpireStatus { kind: EffectType::Poison, .. } = effect {
+                expired_at = Some(now);
+            }
+        }
+    }
+
+    // Applied at tick 0 with duration 3, so it must wear off at tick 3 - long before the
+    // applier itself (which overshot to tick 100) would ever update again.
+    assert_eq!(expired_at, Some(3));
+}
+
+struct Shouter {
+    target: usize
+}
+
+impl Updatable for Shouter {
+    fn update(&mut self, effects: &mut Vec<Effect>) -> Option<u32> {
+        effects.push(Effect::Send {
+            target: self.target,
+            message: Message::normal(vec![Text::normal("Something moved nearby.")])
+        });
+
+        None
+    }
+
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn deactivate(&mut self) {
+        unimplemented!()
+    }
+}
+
+struct Listener {
+    received_count: u32,
+    updates: u32
+}
+
+impl Updatable for Listener {
+    fn update(&mut self, effects: &mut Vec<Effect>) -> Option<u32> {
+        self.updates += 1;
+
+        effects.push(Effect::Log(Message::normal(vec![Text::normal(&format!(
+            "update {} saw {} mailbox messages",
+            self.updates,
+            self.received_count
+        ))])));
+
+        self.received_count = 0;
+
+        if self.updates < 3 {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn deactivate(&mut self) {
+        unimplemented!()
+    }
+
+    fn receive_mail(&mut self, messages: Vec<Message>) {
+        self.received_count += messages.len() as u32;
+    }
+}
+
+#[test]
+fn mailbox_delivers_sent_messages() {
+    let mut queue = UpdateQueue::new();
+    let mut shouter = Shouter { target: 1 };
+    let mut listener = Listener { received_count: 0, updates: 0 };
+
+    queue.push(0, 0, 0, &mut shouter);
+    queue.push(1, 1, 0, &mut listener);
+
+    let mut effects: Vec<Effect> = Vec::new();
+    let mut logs: Vec<Message> = Vec::new();
+
+    while let Some(_) = queue.update(&mut effects) {
+        for effect in effects.drain(..) {
+            if let Effect::Log(msg) = effect {
+                logs.push(msg);
+            }
+        }
+    }
+
+    // The shouter's message, sent on tick 0, must already be waiting in entity 1's mailbox by
+    // the time entity 1 is first updated on tick 1.
+    assert_eq!(logs[0].render_plain(), "update 1 saw 1 mailbox messages");
+    assert_eq!(logs[1].render_plain(), "update 2 saw 0 mailbox messages");
+    assert_eq!(logs[2].render_plain(), "update 3 saw 0 mailbox messages");
+}
+
+#[test]
+fn mailbox_is_not_redelivered_when_caller_batches_effects() {
+    // A front-end that only drains `effects` once per render frame (rather than after every
+    // single `update()` call) must not see a `Send` redelivered on every later tick just
+    // because it's still sitting in the accumulated vector.
+    let mut queue = UpdateQueue::new();
+    let mut shouter = Shouter { target: 1 };
+    let mut listener = Listener { received_count: 0, updates: 0 };
+
+    queue.push(0, 0, 0, &mut shouter);
+    queue.push(1, 1, 0, &mut listener);
+
+    // Intentionally never drained mid-loop, so leftover effects from earlier calls are still
+    // sitting in the vector when later calls scan it for newly produced `Send`s.
+    let mut effects: Vec<Effect> = Vec::new();
+
+    while let Some(_) = queue.update(&mut effects) { }
+
+    let logs: Vec<String> = effects.iter()
+        .filter_map(|effect| match effect {
+            Effect::Log(msg) => Some(msg.render_plain()),
+            _ => None
+        })
+        .collect();
+
+    assert_eq!(logs[0], "update 1 saw 1 mailbox messages");
+    assert_eq!(logs[1], "update 2 saw 0 mailbox messages");
+    assert_eq!(logs[2], "update 3 saw 0 mailbox messages");
 }
\ No newline at end of file